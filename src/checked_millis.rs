@@ -0,0 +1,46 @@
+use std::ops::{Add, Sub};
+
+/// A millisecond offset whose arithmetic never panics or wraps:
+/// `Add`/`Sub` return `None` on overflow/underflow instead, the way
+/// size arithmetic is commonly guarded when parsing untrusted input.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CheckedMillis(pub u64);
+
+impl CheckedMillis {
+  pub fn get(self) -> u64 {
+    self.0
+  }
+}
+
+impl Add for CheckedMillis {
+  type Output = Option<CheckedMillis>;
+
+  fn add(self, rhs: CheckedMillis) -> Option<CheckedMillis> {
+    self.0.checked_add(rhs.0).map(CheckedMillis)
+  }
+}
+
+impl Sub for CheckedMillis {
+  type Output = Option<CheckedMillis>;
+
+  fn sub(self, rhs: CheckedMillis) -> Option<CheckedMillis> {
+    self.0.checked_sub(rhs.0).map(CheckedMillis)
+  }
+}
+
+#[test]
+fn add_overflow_returns_none() {
+  assert_eq!(CheckedMillis(u64::max_value()) + CheckedMillis(1), None);
+}
+
+#[test]
+fn sub_underflow_returns_none() {
+  assert_eq!(CheckedMillis(0) - CheckedMillis(1), None);
+}
+
+#[test]
+fn add_and_sub_within_range() {
+  assert_eq!(CheckedMillis(5) + CheckedMillis(3), Some(CheckedMillis(8)));
+  assert_eq!(CheckedMillis(5) - CheckedMillis(3), Some(CheckedMillis(2)));
+}
@@ -6,6 +6,16 @@ pub struct Duration {
   pub value: u64,
   #[serde(default = "DurationUnit::default")]
   pub unit: DurationUnit,
+  /// Timecode string, used when `unit` is `timecode`. Accepts the colon
+  /// form `HH:MM:SS`, `MM:SS`, `:SS`, with an optional fractional-seconds
+  /// tail written with either a period or a comma (`00:01:30,500`), or
+  /// the SMPTE frame form `HH:MM:SS:FF` / `HH:MM:SS;FF`. Missing
+  /// higher-order fields default to zero.
+  pub timecode: Option<String>,
+  /// Frame rate used to convert the `FF` component of a SMPTE frame
+  /// timecode to milliseconds. Required for the `HH:MM:SS:FF` /
+  /// `HH:MM:SS;FF` forms; ignored by the colon-with-decimal forms.
+  pub frame_rate: Option<f64>,
 }
 
 impl Default for Duration {
@@ -13,6 +23,8 @@ impl Default for Duration {
     Duration {
       value: default_duration(),
       unit: DurationUnit::default(),
+      timecode: None,
+      frame_rate: None,
     }
   }
 }
@@ -23,6 +35,11 @@ impl Duration {
       DurationUnit::Millisecond => self.value,
       DurationUnit::Second => self.value * 1000,
       DurationUnit::Percent => media_duration * self.value / 100,
+      DurationUnit::Timecode => self
+        .timecode
+        .as_ref()
+        .and_then(|timecode| parse_timecode(timecode, self.frame_rate))
+        .unwrap_or(0),
     }
   }
 }
@@ -31,6 +48,89 @@ fn default_duration() -> u64 {
   1
 }
 
+/// Parses a SMPTE timecode or a `HH:MM:SS[.,]mmm` string into an absolute
+/// number of milliseconds.
+fn parse_timecode(timecode: &str, frame_rate: Option<f64>) -> Option<u64> {
+  let normalized = timecode.replace(';', ":");
+  let fields: Vec<&str> = normalized.split(':').collect();
+
+  if fields.len() == 4 {
+    return parse_frame_timecode(&fields, frame_rate);
+  }
+
+  parse_decimal_timecode(&fields)
+}
+
+/// Parses the `HH:MM:SS:FF` / `HH:MM:SS;FF` frame form, converting the
+/// frame count through the companion `frame_rate`.
+fn parse_frame_timecode(fields: &[&str], frame_rate: Option<f64>) -> Option<u64> {
+  let frame_rate = frame_rate?;
+  let hours: u64 = fields[0].parse().ok()?;
+  let minutes: u64 = fields[1].parse().ok()?;
+  let seconds: u64 = fields[2].parse().ok()?;
+  let frames: u64 = fields[3].parse().ok()?;
+
+  let whole_seconds_ms = (hours * 3600 + minutes * 60 + seconds) * 1000;
+  let frames_ms = (frames * 1000) as f64 / frame_rate;
+
+  Some(whole_seconds_ms + frames_ms as u64)
+}
+
+/// Parses `HH:MM:SS`, `MM:SS`, `:SS`, each with an optional
+/// fractional-seconds tail on the last field. Missing higher-order
+/// fields default to zero.
+fn parse_decimal_timecode(fields: &[&str]) -> Option<u64> {
+  if fields.is_empty() || fields.len() > 3 {
+    return None;
+  }
+
+  let (seconds, fractional_ms) = split_fractional_seconds(fields[fields.len() - 1])?;
+
+  let mut whole_fields = Vec::with_capacity(3);
+  for field in &fields[..fields.len() - 1] {
+    whole_fields.push(if field.is_empty() {
+      0
+    } else {
+      field.parse().ok()?
+    });
+  }
+  whole_fields.push(seconds);
+
+  while whole_fields.len() < 3 {
+    whole_fields.insert(0, 0);
+  }
+
+  let hours = whole_fields[0];
+  let minutes = whole_fields[1];
+  let seconds = whole_fields[2];
+
+  Some((hours * 3600 + minutes * 60 + seconds) * 1000 + fractional_ms)
+}
+
+/// Splits a `SS`, `SS.mmm` or `SS,mmm` field into its whole seconds and
+/// fractional-millisecond parts.
+fn split_fractional_seconds(field: &str) -> Option<(u64, u64)> {
+  match field.find(|separator| separator == '.' || separator == ',') {
+    Some(separator_index) => {
+      let seconds: u64 = field[..separator_index].parse().ok()?;
+      let fractional_ms = parse_fractional_ms(&field[separator_index + 1..])?;
+      Some((seconds, fractional_ms))
+    }
+    None => field.parse().ok().map(|seconds| (seconds, 0)),
+  }
+}
+
+/// Converts a fractional-seconds tail (e.g. `5`, `50`, `500`) to
+/// milliseconds by right-padding it to three digits.
+fn parse_fractional_ms(fractional: &str) -> Option<u64> {
+  if fractional.is_empty() {
+    return Some(0);
+  }
+
+  let padded: String = fractional.chars().chain("000".chars()).take(3).collect();
+  padded.parse().ok()
+}
+
 #[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
 pub enum DurationUnit {
   #[serde(rename = "millisecond")]
@@ -39,6 +139,8 @@ pub enum DurationUnit {
   Second,
   #[serde(rename = "percent")]
   Percent,
+  #[serde(rename = "timecode")]
+  Timecode,
 }
 
 impl Default for DurationUnit {
@@ -72,6 +174,7 @@ fn duration_checks() {
   let duration = Duration {
     value: 10,
     unit: DurationUnit::Second,
+    ..Default::default()
   };
 
   let ms_duration: u64 = duration.to_millis(media_duration);
@@ -80,6 +183,7 @@ fn duration_checks() {
   let duration = Duration {
     value: 10,
     unit: DurationUnit::Millisecond,
+    ..Default::default()
   };
 
   let ms_duration: u64 = duration.to_millis(media_duration);
@@ -88,8 +192,82 @@ fn duration_checks() {
   let duration = Duration {
     value: 5,
     unit: DurationUnit::Percent,
+    ..Default::default()
   };
 
   let ms_duration: u64 = duration.to_millis(media_duration);
   assert_eq!(ms_duration, 33);
 }
+
+#[test]
+fn timecode_colon_forms() {
+  let media_duration = 0;
+
+  let duration = Duration {
+    unit: DurationUnit::Timecode,
+    timecode: Some("01:02:03".to_string()),
+    ..Default::default()
+  };
+  assert_eq!(duration.to_millis(media_duration), 3723000);
+
+  let duration = Duration {
+    unit: DurationUnit::Timecode,
+    timecode: Some("02:03".to_string()),
+    ..Default::default()
+  };
+  assert_eq!(duration.to_millis(media_duration), 123000);
+
+  let duration = Duration {
+    unit: DurationUnit::Timecode,
+    timecode: Some(":03".to_string()),
+    ..Default::default()
+  };
+  assert_eq!(duration.to_millis(media_duration), 3000);
+}
+
+#[test]
+fn timecode_with_fractional_seconds() {
+  let media_duration = 0;
+
+  let duration = Duration {
+    unit: DurationUnit::Timecode,
+    timecode: Some("00:01:30.500".to_string()),
+    ..Default::default()
+  };
+  assert_eq!(duration.to_millis(media_duration), 90500);
+
+  let duration = Duration {
+    unit: DurationUnit::Timecode,
+    timecode: Some("00:01:30,5".to_string()),
+    ..Default::default()
+  };
+  assert_eq!(duration.to_millis(media_duration), 90500);
+}
+
+#[test]
+fn timecode_smpte_frame_form() {
+  let media_duration = 0;
+
+  let duration = Duration {
+    unit: DurationUnit::Timecode,
+    timecode: Some("00:00:01:12".to_string()),
+    frame_rate: Some(24.0),
+    ..Default::default()
+  };
+  assert_eq!(duration.to_millis(media_duration), 1500);
+
+  let duration = Duration {
+    unit: DurationUnit::Timecode,
+    timecode: Some("00:00:01;12".to_string()),
+    frame_rate: Some(24.0),
+    ..Default::default()
+  };
+  assert_eq!(duration.to_millis(media_duration), 1500);
+
+  let duration = Duration {
+    unit: DurationUnit::Timecode,
+    timecode: Some("00:00:01:12".to_string()),
+    ..Default::default()
+  };
+  assert_eq!(duration.to_millis(media_duration), 0);
+}
@@ -0,0 +1,68 @@
+use stainless_ffmpeg::format_context::FormatContext;
+
+/// Locates the primary video stream, if any. Pair with
+/// [`keyframe_timestamp_ms`] in a caller-owned packet loop, since
+/// `FormatContext` has no seek/rewind and can only be scanned once.
+pub fn find_video_stream_index(format_context: &FormatContext) -> Option<usize> {
+  (0..format_context.get_nb_streams())
+    .find(|&stream_index| format_context.is_video_stream(stream_index))
+}
+
+/// Converts a packet's PTS to milliseconds using the stream's time base.
+pub fn keyframe_timestamp_ms(pts: i64, time_base: f64) -> u64 {
+  (pts as f64 * time_base * 1000.0) as u64
+}
+
+/// Returns the keyframe timestamp closest to `target`, breaking ties in
+/// favor of the earlier one.
+pub fn closest_keyframe(target: u64, keyframe_timestamps: &[u64]) -> Option<u64> {
+  if keyframe_timestamps.is_empty() {
+    return None;
+  }
+
+  match keyframe_timestamps.binary_search(&target) {
+    Ok(index) => Some(keyframe_timestamps[index]),
+    Err(index) => {
+      let before = index.checked_sub(1).map(|before| keyframe_timestamps[before]);
+      let after = keyframe_timestamps.get(index).copied();
+
+      match (before, after) {
+        (Some(before), Some(after)) => {
+          if target - before <= after - target {
+            Some(before)
+          } else {
+            Some(after)
+          }
+        }
+        (Some(before), None) => Some(before),
+        (None, Some(after)) => Some(after),
+        (None, None) => None,
+      }
+    }
+  }
+}
+
+#[test]
+fn closest_keyframe_exact_match() {
+  let keyframe_timestamps = [0, 2000, 4000, 6000];
+  assert_eq!(closest_keyframe(4000, &keyframe_timestamps), Some(4000));
+}
+
+#[test]
+fn closest_keyframe_rounds_to_nearest() {
+  let keyframe_timestamps = [0, 2000, 4000, 6000];
+  assert_eq!(closest_keyframe(2900, &keyframe_timestamps), Some(2000));
+  assert_eq!(closest_keyframe(3100, &keyframe_timestamps), Some(4000));
+}
+
+#[test]
+fn closest_keyframe_out_of_range() {
+  let keyframe_timestamps = [1000, 2000];
+  assert_eq!(closest_keyframe(0, &keyframe_timestamps), Some(1000));
+  assert_eq!(closest_keyframe(5000, &keyframe_timestamps), Some(2000));
+}
+
+#[test]
+fn closest_keyframe_no_candidates() {
+  assert_eq!(closest_keyframe(1000, &[]), None);
+}
@@ -1,14 +1,19 @@
 #[macro_use]
 extern crate serde_derive;
 
+mod checked_millis;
 mod duration;
+mod keyframes;
 mod message;
+mod playlist;
+mod scte35;
 mod split_policy;
 
 use duration::{Duration, DurationPosition};
 use mcai_worker_sdk::{
   job::JobResult, start_worker, JsonSchema, McaiChannel, MessageError, MessageEvent, Version,
 };
+use playlist::OutputFormat;
 
 macro_rules! crate_version {
   () => {
@@ -32,6 +37,10 @@ pub struct MediaSplitterParameters {
   source_path: String,
   #[serde(default = "default_output_parameter_name")]
   output_parameter_name: String,
+  /// Format used to serialize the computed segments: a plain JSON list
+  /// (the default), or a VOD HLS media playlist (`.m3u8`).
+  #[serde(default = "OutputFormat::default")]
+  output_format: OutputFormat,
 
   /// Number of parts to split into
   #[serde(default = "default_segments")]
@@ -50,9 +59,22 @@ pub struct MediaSplitterParameters {
   /// By default, it is set from the start of the file, but it can also be set from the end.
   #[serde(default = "DurationPosition::default")]
   duration_position: DurationPosition,
-  /// It will add duration to overlap segments.  
-  /// This means some data will be process twice.  
+  /// It will add duration to overlap segments.
+  /// This means some data will be process twice.
   overlap: Option<Duration>,
+  /// Snap segment boundaries to the SCTE-35 splice points (`splice_insert`
+  /// / `time_signal` commands) found in the source, instead of splitting
+  /// at evenly spaced arithmetic divisions. The number of cue points kept
+  /// is capped at `number_of_segments - 1`, so leaving `number_of_segments`
+  /// at its default of 1 yields a single whole-file segment regardless of
+  /// how many cue points were found.
+  #[serde(default)]
+  align_to_scte35: bool,
+  /// Shift each computed segment boundary to the nearest keyframe of the
+  /// primary video stream, so every segment is independently decodable.
+  /// Has no effect when the source has no detectable video stream.
+  #[serde(default)]
+  snap_to_keyframes: bool,
 }
 
 impl MessageEvent<MediaSplitterParameters> for MediaSplitterEvent {
@@ -7,15 +7,34 @@ use mcai_worker_sdk::{
 use stainless_ffmpeg::format_context::FormatContext;
 use std::cmp::min;
 
-use crate::{duration::DurationPosition, split_policy::SplitPolicy, MediaSplitterParameters};
+use crate::{
+  checked_millis::CheckedMillis, duration::DurationPosition, keyframes, playlist,
+  playlist::OutputFormat, scte35, split_policy::SplitPolicy, MediaSplitterParameters,
+};
 
 pub fn process(
   _channel: Option<McaiChannel>,
   parameters: &MediaSplitterParameters,
   job_result: JobResult,
 ) -> Result<JobResult, MessageError> {
-  let media_duration =
-    get_media_duration_in_milliseconds(&parameters.source_path).map_err(|msg| {
+  let (media_duration, cue_points, keyframe_timestamps) = get_media_duration_in_milliseconds(
+    &parameters.source_path,
+    parameters.align_to_scte35,
+    parameters.snap_to_keyframes,
+  )
+  .map_err(|msg| {
+    MessageError::ProcessingError(
+      job_result
+        .clone()
+        .with_status(JobStatus::Error)
+        .with_message(&msg),
+    )
+  })?;
+
+  debug!("Input media duration: {} ms", media_duration);
+
+  let segments = generate_segments(parameters, media_duration, cue_points, keyframe_timestamps)
+    .map_err(|msg| {
       MessageError::ProcessingError(
         job_result
           .clone()
@@ -24,22 +43,36 @@ pub fn process(
       )
     })?;
 
-  debug!("Input media duration: {} ms", media_duration);
-
-  let segments = generate_segments(parameters, media_duration)?;
+  let job_result = job_result.with_status(JobStatus::Completed);
 
-  Ok(
-    job_result
-      .with_status(JobStatus::Completed)
+  let job_result = match parameters.output_format {
+    OutputFormat::Json => job_result
       .with_json(&parameters.output_parameter_name, &segments)
       .map_err(MessageError::RuntimeError)?,
-  )
+    OutputFormat::HlsM3u8 => {
+      let has_overlap = segments
+        .windows(2)
+        .any(|window| window[1].start < window[0].end);
+      let playlist = playlist::to_hls_m3u8(&segments, &parameters.source_path, has_overlap);
+      job_result
+        .with_json(&parameters.output_parameter_name, &playlist)
+        .map_err(MessageError::RuntimeError)?
+    }
+  };
+
+  Ok(job_result)
 }
 
 fn generate_segments(
   parameters: &MediaSplitterParameters,
   media_duration: u64,
-) -> Result<Vec<MediaSegment>, MessageError> {
+  cue_points: Vec<u64>,
+  keyframe_timestamps: Vec<u64>,
+) -> Result<Vec<MediaSegment>, String> {
+  if media_duration == 0 {
+    return Err("unable to split a zero-length media".to_string());
+  }
+
   let total_duration = if let Some(duration) = &parameters.duration {
     min(duration.clone().to_millis(media_duration), media_duration)
   } else {
@@ -64,13 +97,25 @@ fn generate_segments(
 
   let start_offset = match parameters.duration_position {
     DurationPosition::Start => 0,
-    DurationPosition::End => media_duration - total_duration,
+    DurationPosition::End => (CheckedMillis(media_duration) - CheckedMillis(total_duration))
+      .unwrap_or(CheckedMillis(0))
+      .get(),
   };
 
-  Ok(split_policy.split(total_duration, start_offset, segment_overlap))
+  split_policy.split(
+    total_duration,
+    start_offset,
+    segment_overlap,
+    cue_points,
+    keyframe_timestamps,
+  )
 }
 
-fn get_media_duration_in_milliseconds(path: &str) -> Result<u64, String> {
+fn get_media_duration_in_milliseconds(
+  path: &str,
+  align_to_scte35: bool,
+  snap_to_keyframes: bool,
+) -> Result<(u64, Vec<u64>, Vec<u64>), String> {
   let mut format_context = FormatContext::new(path)?;
   format_context.open_input()?;
 
@@ -79,8 +124,52 @@ fn get_media_duration_in_milliseconds(path: &str) -> Result<u64, String> {
     .map(|duration| duration as u64 * 1000)
     .unwrap_or_else(|| 0);
 
+  let scte35_stream_index = if align_to_scte35 {
+    scte35::find_scte35_stream_index(&format_context)
+  } else {
+    None
+  };
+
+  let video_stream_index = if snap_to_keyframes {
+    keyframes::find_video_stream_index(&format_context)
+  } else {
+    None
+  };
+
+  let time_base =
+    video_stream_index.map(|stream_index| format_context.get_stream_time_base(stream_index));
+
+  // `FormatContext` can't seek/rewind, so both are collected in one pass.
+
+  let mut cue_points = Vec::new();
+  let mut keyframe_timestamps = Vec::new();
+
+  if scte35_stream_index.is_some() || video_stream_index.is_some() {
+    while let Ok(packet) = format_context.next_packet() {
+      let stream_index = packet.get_stream_index() as usize;
+
+      if Some(stream_index) == scte35_stream_index {
+        if let Some(pts_ms) = scte35::cue_point_from_packet(packet.get_data()) {
+          cue_points.push(pts_ms);
+        }
+      }
+
+      if Some(stream_index) == video_stream_index && packet.is_key() {
+        keyframe_timestamps.push(keyframes::keyframe_timestamp_ms(
+          packet.get_pts(),
+          time_base.unwrap(),
+        ));
+      }
+    }
+
+    cue_points.sort_unstable();
+    cue_points.dedup();
+    keyframe_timestamps.sort_unstable();
+    keyframe_timestamps.dedup();
+  }
+
   format_context.close_input();
-  Ok(duration_millisec)
+  Ok((duration_millisec, cue_points, keyframe_timestamps))
 }
 
 #[test]
@@ -93,7 +182,7 @@ fn default() {
   };
   println!("{:?}", parameters);
 
-  let segments = generate_segments(&parameters, 10 * 1000).unwrap();
+  let segments = generate_segments(&parameters, 10 * 1000, Vec::new(), Vec::new()).unwrap();
   assert_eq!(
     segments,
     [MediaSegment {
@@ -114,11 +203,12 @@ fn duration() {
     duration: Some(Duration {
       value: 5,
       unit: DurationUnit::Percent,
+      ..Default::default()
     }),
     ..Default::default()
   };
 
-  let segments = generate_segments(&parameters, 10 * 1000).unwrap();
+  let segments = generate_segments(&parameters, 10 * 1000, Vec::new(), Vec::new()).unwrap();
   assert_eq!(segments, vec![MediaSegment { start: 0, end: 499 }]);
 }
 
@@ -133,11 +223,12 @@ fn max_duration() {
     max_duration: Some(Duration {
       value: 5,
       unit: DurationUnit::Second,
+      ..Default::default()
     }),
     ..Default::default()
   };
 
-  let segments = generate_segments(&parameters, 10 * 1000).unwrap();
+  let segments = generate_segments(&parameters, 10 * 1000, Vec::new(), Vec::new()).unwrap();
   assert_eq!(
     segments,
     vec![MediaSegment {
@@ -158,15 +249,17 @@ fn duration_max_duration() {
     duration: Some(Duration {
       value: 5,
       unit: DurationUnit::Percent,
+      ..Default::default()
     }),
     max_duration: Some(Duration {
       value: 5,
       unit: DurationUnit::Second,
+      ..Default::default()
     }),
     ..Default::default()
   };
 
-  let segments = generate_segments(&parameters, 10 * 1000).unwrap();
+  let segments = generate_segments(&parameters, 10 * 1000, Vec::new(), Vec::new()).unwrap();
   assert_eq!(
     segments,
     vec![
@@ -185,15 +278,17 @@ fn duration_max_duration() {
     duration: Some(Duration {
       value: 60,
       unit: DurationUnit::Percent,
+      ..Default::default()
     }),
     max_duration: Some(Duration {
       value: 5,
       unit: DurationUnit::Second,
+      ..Default::default()
     }),
     ..Default::default()
   };
 
-  let segments = generate_segments(&parameters, 10 * 1000).unwrap();
+  let segments = generate_segments(&parameters, 10 * 1000, Vec::new(), Vec::new()).unwrap();
   assert_eq!(
     segments,
     vec![
@@ -220,16 +315,18 @@ fn duration_at_the_end() {
     duration: Some(Duration {
       value: 5,
       unit: DurationUnit::Percent,
+      ..Default::default()
     }),
     max_duration: Some(Duration {
       value: 5,
       unit: DurationUnit::Second,
+      ..Default::default()
     }),
     duration_position: DurationPosition::End,
     ..Default::default()
   };
 
-  let segments = generate_segments(&parameters, 10 * 1000).unwrap();
+  let segments = generate_segments(&parameters, 10 * 1000, Vec::new(), Vec::new()).unwrap();
   assert_eq!(
     segments,
     vec![
@@ -251,15 +348,17 @@ fn duration_at_the_end() {
     duration: Some(Duration {
       value: 60,
       unit: DurationUnit::Percent,
+      ..Default::default()
     }),
     max_duration: Some(Duration {
       value: 5,
       unit: DurationUnit::Second,
+      ..Default::default()
     }),
     ..Default::default()
   };
 
-  let segments = generate_segments(&parameters, 10 * 1000).unwrap();
+  let segments = generate_segments(&parameters, 10 * 1000, Vec::new(), Vec::new()).unwrap();
   assert_eq!(
     segments,
     vec![
@@ -274,3 +373,37 @@ fn duration_at_the_end() {
     ]
   );
 }
+
+#[test]
+fn zero_length_media_is_rejected() {
+  let parameters = MediaSplitterParameters {
+    source_path: "fake_source.mxf".to_string(),
+    output_parameter_name: crate::default_output_parameter_name(),
+    number_of_segments: 1,
+    ..Default::default()
+  };
+
+  let result = generate_segments(&parameters, 0, Vec::new(), Vec::new());
+  assert!(result.is_err());
+}
+
+#[test]
+fn duration_larger_than_media_duration_at_the_end() {
+  use crate::duration::{Duration, DurationPosition, DurationUnit};
+
+  let parameters = MediaSplitterParameters {
+    source_path: "fake_source.mxf".to_string(),
+    output_parameter_name: crate::default_output_parameter_name(),
+    number_of_segments: 1,
+    duration: Some(Duration {
+      value: 20,
+      unit: DurationUnit::Second,
+      ..Default::default()
+    }),
+    duration_position: DurationPosition::End,
+    ..Default::default()
+  };
+
+  let segments = generate_segments(&parameters, 10 * 1000, Vec::new(), Vec::new()).unwrap();
+  assert_eq!(segments, vec![MediaSegment { start: 0, end: 9999 }]);
+}
@@ -0,0 +1,106 @@
+use mcai_worker_sdk::{parameter::media_segment::MediaSegment, JsonSchema};
+
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub enum OutputFormat {
+  #[serde(rename = "json")]
+  Json,
+  #[serde(rename = "hls_m3u8")]
+  HlsM3u8,
+}
+
+impl Default for OutputFormat {
+  fn default() -> OutputFormat {
+    OutputFormat::Json
+  }
+}
+
+const HLS_VERSION: u8 = 3;
+
+/// Renders the computed segments as a VOD HLS media playlist, with a
+/// segment URI derived from `source_path` and the segment index.
+/// When `has_overlap` is set, consecutive segments are no longer
+/// contiguous in time, so a discontinuity tag is emitted before each
+/// segment after the first.
+pub fn to_hls_m3u8(segments: &[MediaSegment], source_path: &str, has_overlap: bool) -> String {
+  let target_duration_in_seconds = segments
+    .iter()
+    .map(|segment| segment.end - segment.start)
+    .max()
+    .map(|duration_in_ms| ((duration_in_ms as f64) / 1000.0).ceil() as u64)
+    .unwrap_or(0);
+
+  let mut playlist = String::new();
+  playlist.push_str("#EXTM3U\n");
+  playlist.push_str(&format!("#EXT-X-VERSION:{}\n", HLS_VERSION));
+  playlist.push_str(&format!(
+    "#EXT-X-TARGETDURATION:{}\n",
+    target_duration_in_seconds
+  ));
+  playlist.push_str("#EXT-X-MEDIA-SEQUENCE:0\n");
+
+  for (index, segment) in segments.iter().enumerate() {
+    if has_overlap && index > 0 {
+      playlist.push_str("#EXT-X-DISCONTINUITY\n");
+    }
+
+    let segment_duration_in_seconds = (segment.end - segment.start) as f64 / 1000.0;
+    playlist.push_str(&format!("#EXTINF:{:.3},\n", segment_duration_in_seconds));
+    playlist.push_str(&format!("{}.{}.ts\n", source_path, index));
+  }
+
+  playlist.push_str("#EXT-X-ENDLIST\n");
+  playlist
+}
+
+#[test]
+fn hls_m3u8_without_overlap() {
+  let segments = vec![
+    MediaSegment { start: 0, end: 5000 },
+    MediaSegment {
+      start: 5000,
+      end: 9500,
+    },
+  ];
+
+  let playlist = to_hls_m3u8(&segments, "source.mxf", false);
+
+  assert_eq!(
+    playlist,
+    "#EXTM3U\n\
+#EXT-X-VERSION:3\n\
+#EXT-X-TARGETDURATION:5\n\
+#EXT-X-MEDIA-SEQUENCE:0\n\
+#EXTINF:5.000,\n\
+source.mxf.0.ts\n\
+#EXTINF:4.500,\n\
+source.mxf.1.ts\n\
+#EXT-X-ENDLIST\n"
+  );
+}
+
+#[test]
+fn hls_m3u8_with_overlap() {
+  let segments = vec![
+    MediaSegment { start: 0, end: 5000 },
+    MediaSegment {
+      start: 4000,
+      end: 9500,
+    },
+  ];
+
+  let playlist = to_hls_m3u8(&segments, "source.mxf", true);
+
+  assert_eq!(
+    playlist,
+    "#EXTM3U\n\
+#EXT-X-VERSION:3\n\
+#EXT-X-TARGETDURATION:6\n\
+#EXT-X-MEDIA-SEQUENCE:0\n\
+#EXTINF:5.000,\n\
+source.mxf.0.ts\n\
+#EXT-X-DISCONTINUITY\n\
+#EXTINF:5.500,\n\
+source.mxf.1.ts\n\
+#EXT-X-ENDLIST\n"
+  );
+}
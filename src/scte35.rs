@@ -0,0 +1,105 @@
+use stainless_ffmpeg::format_context::FormatContext;
+
+const SPLICE_INSERT: u8 = 0x05;
+const TIME_SIGNAL: u8 = 0x06;
+
+/// Locates the MPEG-TS stream carrying SCTE-35 signalling, if any. Pair
+/// with [`cue_point_from_packet`] in a caller-owned packet loop, since
+/// `FormatContext` has no seek/rewind and can only be scanned once.
+pub fn find_scte35_stream_index(format_context: &FormatContext) -> Option<usize> {
+  (0..format_context.get_nb_streams())
+    .find(|&stream_index| is_scte35_stream(format_context, stream_index))
+}
+
+/// Parses a packet's payload as a `splice_info_section` and, for the
+/// splice commands we support, returns the carried `splice_time`
+/// converted to milliseconds.
+pub fn cue_point_from_packet(data: &[u8]) -> Option<u64> {
+  parse_splice_info_section(data)
+}
+
+fn is_scte35_stream(format_context: &FormatContext, stream_index: usize) -> bool {
+  format_context
+    .get_codec_name(stream_index)
+    .map(|codec_name| codec_name == "scte_35")
+    .unwrap_or(false)
+}
+
+/// Parses a `splice_info_section` payload and, for the splice commands we
+/// support, returns the carried `splice_time` converted from the 90 kHz
+/// clock to milliseconds.
+fn parse_splice_info_section(data: &[u8]) -> Option<u64> {
+  if data.len() < 14 {
+    return None;
+  }
+
+  let splice_command_type = data[13];
+
+  match splice_command_type {
+    SPLICE_INSERT => {
+      // `splice_time()` follows the 32-bit `splice_event_id` and two flag
+      // bytes, and is only present for a non-cancelled program splice that
+      // is not immediate.
+      let cancelled = *data.get(18)? & 0b1000_0000 != 0;
+      let flags = *data.get(19)?;
+      if cancelled || flags & 0b0100_0000 == 0 || flags & 0b0001_0000 != 0 {
+        return None;
+      }
+      parse_splice_time(data.get(20..)?).map(|pts_90k| pts_90k / 90)
+    }
+    TIME_SIGNAL => parse_splice_time(&data[14..]).map(|pts_90k| pts_90k / 90),
+    _ => None,
+  }
+}
+
+/// Reads a `splice_time()` structure: a `time_specified_flag` bit followed,
+/// when set, by a 33-bit PTS on the 90 kHz clock.
+fn parse_splice_time(data: &[u8]) -> Option<u64> {
+  let first_byte = *data.first()?;
+  let time_specified_flag = first_byte & 0b1000_0000 != 0;
+
+  if !time_specified_flag || data.len() < 5 {
+    return None;
+  }
+
+  let pts_90k = (u64::from(first_byte & 0b0000_0001) << 32)
+    | (u64::from(data[1]) << 24)
+    | (u64::from(data[2]) << 16)
+    | (u64::from(data[3]) << 8)
+    | u64::from(data[4]);
+
+  Some(pts_90k)
+}
+
+#[test]
+fn splice_time_with_pts() {
+  let data = [0b1000_0001, 0x12, 0x34, 0x56, 0x78];
+  assert_eq!(parse_splice_time(&data), Some(0x1_1234_5678));
+}
+
+#[test]
+fn splice_time_without_pts() {
+  let data = [0b0000_0000];
+  assert_eq!(parse_splice_time(&data), None);
+}
+
+#[test]
+fn splice_info_section_too_short() {
+  let data = [0u8; 10];
+  assert_eq!(parse_splice_info_section(&data), None);
+}
+
+#[test]
+fn splice_info_section_unsupported_command() {
+  let mut data = vec![0u8; 14];
+  data[13] = 0xff;
+  assert_eq!(parse_splice_info_section(&data), None);
+}
+
+#[test]
+fn splice_info_section_time_signal() {
+  let mut data = vec![0u8; 14];
+  data[13] = TIME_SIGNAL;
+  data.extend_from_slice(&[0b1000_0000, 0x00, 0x00, 0x03, 0x84]);
+  assert_eq!(parse_splice_info_section(&data), Some(900 / 90));
+}
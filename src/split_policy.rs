@@ -1,4 +1,4 @@
-use crate::MediaSplitterParameters;
+use crate::{checked_millis::CheckedMillis, keyframes::closest_keyframe, MediaSplitterParameters};
 use mcai_worker_sdk::{info, parameter::media_segment::MediaSegment};
 use std::cmp::min;
 
@@ -35,7 +35,13 @@ impl SplitPolicy {
     media_duration: u64,
     start_offset: u64,
     segment_overlap: Option<u64>,
-  ) -> Vec<MediaSegment> {
+    cue_points: Vec<u64>,
+    keyframe_timestamps: Vec<u64>,
+  ) -> Result<Vec<MediaSegment>, String> {
+    if !cue_points.is_empty() {
+      return self.split_on_cue_points(media_duration, start_offset, cue_points);
+    }
+
     let mut number_of_segments = self.number_of_segments;
 
     if let Some(min_segment_duration) = &self.min_segment_duration {
@@ -51,32 +57,60 @@ impl SplitPolicy {
 
     let overlap = segment_overlap.unwrap_or(0);
 
-    let mut segments = Vec::with_capacity(number_of_segments as usize);
-    let mut next_start = 0;
+    let mut ends = Vec::with_capacity(number_of_segments as usize);
     let mut next_end = 0;
 
     for segment_index in 0..number_of_segments {
-      let next_segment_duration = if media_duration - next_end == number_of_segments {
+      let remaining_duration = (CheckedMillis(media_duration) - CheckedMillis(next_end))
+        .ok_or_else(|| {
+          format!(
+            "segment boundary ({} ms) went past the media duration ({} ms)",
+            next_end, media_duration
+          )
+        })?
+        .get();
+
+      let next_segment_duration = if remaining_duration == number_of_segments {
         1
       } else {
-        let remaining_duration = media_duration - next_end;
         let remaining_segments = number_of_segments - segment_index;
         (remaining_duration as f64 / remaining_segments as f64) as u64
       };
 
-      next_end += next_segment_duration as u64;
+      next_end = (CheckedMillis(next_end) + CheckedMillis(next_segment_duration))
+        .ok_or_else(|| {
+          format!(
+            "segment boundary overflowed while splitting a {} ms media",
+            media_duration
+          )
+        })?
+        .get();
+
       if next_end >= media_duration {
         next_end = media_duration;
       }
 
-      segments.push(MediaSegment::new(
-        next_start + start_offset,
-        next_end + start_offset,
-      ));
+      ends.push(next_end);
 
       if next_end >= media_duration {
         break;
       }
+    }
+
+    if !keyframe_timestamps.is_empty() {
+      let window_relative_keyframes =
+        to_window_relative(&keyframe_timestamps, start_offset, media_duration);
+      ends = snap_ends_to_keyframes(ends, media_duration, &window_relative_keyframes);
+    }
+
+    let mut segments = Vec::with_capacity(ends.len());
+    let mut next_start = 0;
+
+    for next_end in ends {
+      segments.push(MediaSegment::new(
+        next_start + start_offset,
+        next_end + start_offset,
+      ));
 
       next_start = if next_end < overlap {
         0
@@ -85,16 +119,180 @@ impl SplitPolicy {
       };
     }
 
-    segments
+    Ok(segments)
+  }
+
+  /// Places segment boundaries on the given SCTE-35 cue points instead of
+  /// on evenly spaced arithmetic divisions. When there are more cue
+  /// points than the `number_of_segments - 1` interior boundaries needed,
+  /// keeps the most evenly distributed ones; when there are fewer,
+  /// subdivides the longest resulting intervals until the target count is
+  /// reached.
+  fn split_on_cue_points(
+    self,
+    media_duration: u64,
+    start_offset: u64,
+    cue_points: Vec<u64>,
+  ) -> Result<Vec<MediaSegment>, String> {
+    let window_relative_cue_points =
+      to_window_relative(&cue_points, start_offset, media_duration);
+
+    let mut boundaries: Vec<u64> = window_relative_cue_points
+      .into_iter()
+      .filter(|&cue_point| cue_point > 0 && cue_point < media_duration)
+      .collect();
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let target_boundaries = (self.number_of_segments.max(1) - 1) as usize;
+
+    if boundaries.len() > target_boundaries {
+      boundaries =
+        keep_most_evenly_distributed(boundaries, media_duration, target_boundaries);
+    } else {
+      boundaries = subdivide_longest_intervals(boundaries, media_duration, target_boundaries)?;
+    }
+
+    let mut bounds = Vec::with_capacity(boundaries.len() + 2);
+    bounds.push(0);
+    bounds.extend(boundaries);
+    bounds.push(media_duration);
+
+    Ok(
+      bounds
+        .windows(2)
+        .map(|bound| MediaSegment::new(bound[0] + start_offset, bound[1] + start_offset))
+        .collect(),
+    )
   }
 }
 
+/// Converts file-absolute timestamps (cue points, keyframes) into
+/// coordinates relative to the split window that starts at
+/// `start_offset`, dropping timestamps that fall outside
+/// `[start_offset, start_offset + media_duration]`.
+fn to_window_relative(
+  timestamps: &[u64],
+  start_offset: u64,
+  media_duration: u64,
+) -> Vec<u64> {
+  timestamps
+    .iter()
+    .filter_map(|&timestamp| {
+      (CheckedMillis(timestamp) - CheckedMillis(start_offset)).map(CheckedMillis::get)
+    })
+    .filter(|&timestamp| timestamp <= media_duration)
+    .collect()
+}
+
+/// Keeps `target_len` cue points out of `boundaries`, picking for each of
+/// the `target_len` evenly spaced ideal times across `media_duration` the
+/// closest remaining candidate, so the kept boundaries are spread evenly
+/// across the media rather than across the candidate list's indices.
+fn keep_most_evenly_distributed(
+  boundaries: Vec<u64>,
+  media_duration: u64,
+  target_len: usize,
+) -> Vec<u64> {
+  if boundaries.len() <= target_len || target_len == 0 {
+    return boundaries.into_iter().take(target_len).collect();
+  }
+
+  let mut remaining = boundaries;
+  let mut chosen = Vec::with_capacity(target_len);
+
+  for slot in 1..=target_len as u64 {
+    let ideal_time = slot * media_duration / (target_len as u64 + 1);
+
+    let closest_index = remaining
+      .iter()
+      .enumerate()
+      .min_by_key(|(_, &candidate)| candidate.abs_diff(ideal_time))
+      .map(|(index, _)| index)
+      .expect("remaining has at least target_len candidates left");
+
+    chosen.push(remaining.remove(closest_index));
+  }
+
+  chosen.sort_unstable();
+  chosen
+}
+
+/// Adds extra boundaries, splitting the longest interval in half each
+/// time, until `boundaries` (plus the implicit start/end bounds) reaches
+/// `target_len` interior boundaries.
+fn subdivide_longest_intervals(
+  mut boundaries: Vec<u64>,
+  media_duration: u64,
+  target_len: usize,
+) -> Result<Vec<u64>, String> {
+  while boundaries.len() < target_len {
+    let mut bounds = Vec::with_capacity(boundaries.len() + 2);
+    bounds.push(0);
+    bounds.extend(boundaries.iter().copied());
+    bounds.push(media_duration);
+
+    let longest_interval = bounds.windows(2).enumerate().max_by_key(|(_, bound)| {
+      (CheckedMillis(bound[1]) - CheckedMillis(bound[0]))
+        .map(CheckedMillis::get)
+        .unwrap_or(0)
+    });
+
+    let (longest_index, bound) = match longest_interval {
+      Some(longest_interval) => longest_interval,
+      None => break,
+    };
+
+    let interval_len = (CheckedMillis(bound[1]) - CheckedMillis(bound[0]))
+      .ok_or_else(|| format!("cue point boundary {} precedes {}", bound[1], bound[0]))?
+      .get();
+
+    if interval_len < 2 {
+      break;
+    }
+
+    let midpoint = (CheckedMillis(bound[0]) + CheckedMillis(bound[1]))
+      .ok_or_else(|| format!("cue point boundaries overflowed past {} ms", media_duration))?
+      .get()
+      / 2;
+    boundaries.insert(longest_index, midpoint);
+  }
+
+  Ok(boundaries)
+}
+
+/// Shifts every segment end but the last (the true end of the media) to
+/// its nearest keyframe timestamp, guaranteeing strictly increasing,
+/// non-empty segments: if two ends snap to the same keyframe, the empty
+/// segment that would result is simply dropped.
+fn snap_ends_to_keyframes(
+  ends: Vec<u64>,
+  media_duration: u64,
+  keyframe_timestamps: &[u64],
+) -> Vec<u64> {
+  let last_end = match ends.last() {
+    Some(&last_end) => last_end,
+    None => return ends,
+  };
+
+  let mut snapped_ends: Vec<u64> = ends[..ends.len() - 1]
+    .iter()
+    .filter_map(|&end| closest_keyframe(end, keyframe_timestamps))
+    .filter(|&end| end > 0 && end < media_duration)
+    .collect();
+
+  snapped_ends.sort_unstable();
+  snapped_ends.dedup();
+  snapped_ends.push(last_end);
+  snapped_ends
+}
+
 #[test]
 pub fn empty_parameters() {
   let media_duration = 100;
   let split_policy = SplitPolicy::default();
 
-  let segments = split_policy.split(media_duration, 0, None);
+  let segments = split_policy.split(media_duration, 0, None, Vec::new(), Vec::new()).unwrap();
 
   assert_eq!(1, segments.len());
   assert_eq!(segments, vec![MediaSegment { start: 0, end: 100 }]);
@@ -108,7 +306,7 @@ pub fn segments() {
     min_segment_duration: None,
   };
 
-  let segments = split_policy.split(media_duration, 0, None);
+  let segments = split_policy.split(media_duration, 0, None, Vec::new(), Vec::new()).unwrap();
 
   assert_eq!(3, segments.len());
   assert_eq!(
@@ -132,7 +330,7 @@ pub fn number_of_segments_upper_than_duration() {
     min_segment_duration: None,
   };
 
-  let segments = split_policy.split(media_duration, 0, None);
+  let segments = split_policy.split(media_duration, 0, None, Vec::new(), Vec::new()).unwrap();
 
   assert_eq!(10, segments.len());
   assert_eq!(
@@ -157,7 +355,7 @@ pub fn number_of_segments_upper_than_duration() {
     min_segment_duration: None,
   };
 
-  let segments = split_policy.split(media_duration, 0, None);
+  let segments = split_policy.split(media_duration, 0, None, Vec::new(), Vec::new()).unwrap();
 
   assert_eq!(10, segments.len());
   assert_eq!(
@@ -182,7 +380,7 @@ pub fn number_of_segments_upper_than_duration() {
     min_segment_duration: None,
   };
 
-  let segments = split_policy.split(media_duration, 0, None);
+  let segments = split_policy.split(media_duration, 0, None, Vec::new(), Vec::new()).unwrap();
 
   assert_eq!(6, segments.len());
   assert_eq!(
@@ -206,7 +404,7 @@ fn min_segment_duration() {
     min_segment_duration: Some(40),
   };
 
-  let segments = split_policy.split(media_duration, 0, None);
+  let segments = split_policy.split(media_duration, 0, None, Vec::new(), Vec::new()).unwrap();
 
   assert_eq!(1, segments.len());
   assert_eq!(segments, vec![MediaSegment { start: 0, end: 100 }]);
@@ -220,7 +418,7 @@ fn min_segment_duration_with_segments() {
     min_segment_duration: Some(10),
   };
 
-  let segments = split_policy.split(media_duration, 0, None);
+  let segments = split_policy.split(media_duration, 0, None, Vec::new(), Vec::new()).unwrap();
 
   assert_eq!(5, segments.len());
   assert_eq!(
@@ -246,7 +444,7 @@ fn overlap() {
     min_segment_duration: None,
   };
 
-  let segments = split_policy.split(media_duration, 0, Some(5));
+  let segments = split_policy.split(media_duration, 0, Some(5), Vec::new(), Vec::new()).unwrap();
 
   assert_eq!(5, segments.len());
   assert_eq!(
@@ -272,7 +470,7 @@ fn offset() {
     min_segment_duration: None,
   };
 
-  let segments = split_policy.split(media_duration, 30, None);
+  let segments = split_policy.split(media_duration, 30, None, Vec::new(), Vec::new()).unwrap();
 
   assert_eq!(3, segments.len());
   assert_eq!(
@@ -287,3 +485,190 @@ fn offset() {
     ]
   );
 }
+
+#[test]
+fn cue_points_matching_number_of_segments() {
+  let media_duration = 100;
+  let split_policy = SplitPolicy {
+    number_of_segments: 3,
+    min_segment_duration: None,
+  };
+
+  let segments = split_policy.split(media_duration, 0, None, vec![30, 70], Vec::new()).unwrap();
+
+  assert_eq!(
+    segments,
+    vec![
+      MediaSegment { start: 0, end: 30 },
+      MediaSegment { start: 30, end: 70 },
+      MediaSegment {
+        start: 70,
+        end: 100
+      }
+    ]
+  );
+}
+
+#[test]
+fn cue_points_more_than_number_of_segments() {
+  let media_duration = 100;
+  let split_policy = SplitPolicy {
+    number_of_segments: 2,
+    min_segment_duration: None,
+  };
+
+  let segments = split_policy
+    .split(media_duration, 0, None, vec![10, 30, 50, 70, 90], Vec::new())
+    .unwrap();
+
+  assert_eq!(
+    segments,
+    vec![
+      MediaSegment { start: 0, end: 50 },
+      MediaSegment {
+        start: 50,
+        end: 100
+      }
+    ]
+  );
+}
+
+#[test]
+fn cue_points_more_than_number_of_segments_spread_evenly() {
+  let media_duration = 100;
+  let split_policy = SplitPolicy {
+    number_of_segments: 3,
+    min_segment_duration: None,
+  };
+
+  let segments = split_policy
+    .split(media_duration, 0, None, vec![10, 30, 50, 70, 90], Vec::new())
+    .unwrap();
+
+  assert_eq!(
+    segments,
+    vec![
+      MediaSegment { start: 0, end: 30 },
+      MediaSegment { start: 30, end: 70 },
+      MediaSegment {
+        start: 70,
+        end: 100
+      }
+    ]
+  );
+}
+
+#[test]
+fn cue_points_fewer_than_number_of_segments() {
+  let media_duration = 100;
+  let split_policy = SplitPolicy {
+    number_of_segments: 4,
+    min_segment_duration: None,
+  };
+
+  let segments = split_policy.split(media_duration, 0, None, vec![50], Vec::new()).unwrap();
+
+  assert_eq!(4, segments.len());
+  assert_eq!(segments[0].start, 0);
+  assert_eq!(segments[segments.len() - 1].end, 100);
+}
+
+#[test]
+fn snap_to_keyframes() {
+  let media_duration = 100;
+  let split_policy = SplitPolicy {
+    number_of_segments: 3,
+    min_segment_duration: None,
+  };
+
+  let segments = split_policy
+    .split(media_duration, 0, None, Vec::new(), vec![0, 30, 70, 100])
+    .unwrap();
+
+  assert_eq!(3, segments.len());
+  assert_eq!(
+    segments,
+    vec![
+      MediaSegment { start: 0, end: 30 },
+      MediaSegment { start: 30, end: 70 },
+      MediaSegment {
+        start: 70,
+        end: 100
+      }
+    ]
+  );
+}
+
+#[test]
+fn snap_to_keyframes_drops_collisions() {
+  let media_duration = 100;
+  let split_policy = SplitPolicy {
+    number_of_segments: 5,
+    min_segment_duration: None,
+  };
+
+  let segments = split_policy.split(media_duration, 0, None, Vec::new(), vec![0, 40]).unwrap();
+
+  assert_eq!(2, segments.len());
+  assert_eq!(
+    segments,
+    vec![
+      MediaSegment { start: 0, end: 40 },
+      MediaSegment {
+        start: 40,
+        end: 100
+      }
+    ]
+  );
+}
+
+#[test]
+fn snap_to_keyframes_with_start_offset() {
+  let media_duration = 10;
+  let start_offset = 90;
+  let split_policy = SplitPolicy {
+    number_of_segments: 2,
+    min_segment_duration: None,
+  };
+
+  let segments = split_policy
+    .split(media_duration, start_offset, None, Vec::new(), vec![91, 95, 99])
+    .unwrap();
+
+  assert_eq!(
+    segments,
+    vec![
+      MediaSegment { start: 90, end: 95 },
+      MediaSegment {
+        start: 95,
+        end: 100
+      }
+    ]
+  );
+}
+
+#[test]
+fn cue_points_with_start_offset() {
+  let media_duration = 10;
+  let start_offset = 90;
+  let split_policy = SplitPolicy {
+    number_of_segments: 3,
+    min_segment_duration: None,
+  };
+
+  let segments = split_policy
+    .split(media_duration, start_offset, None, vec![50, 91, 95, 99], Vec::new())
+    .unwrap();
+
+  assert_eq!(
+    segments,
+    vec![
+      MediaSegment { start: 90, end: 91 },
+      MediaSegment { start: 91, end: 95 },
+      MediaSegment {
+        start: 95,
+        end: 100
+      }
+    ]
+  );
+}